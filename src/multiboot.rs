@@ -3,26 +3,65 @@
 //  Multiboot Information Struct Parsing
 //
 
+use core::marker::PhantomData;
 use core::ptr;
 
+/// The EAX magic value the bootloader leaves behind when it hands off to the
+/// kernel using the multiboot 2 protocol.
+const MULTIBOOT2_MAGIC: u32 = 0x36d76289;
+
+/// An error that can occur while parsing a multiboot information structure.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MultibootError {
+	/// The EAX magic value passed to the kernel's entry point didn't match
+	/// the multiboot 2 magic number.
+	InvalidMagic,
+
+	/// The pointer to the multiboot information struct wasn't aligned to an
+	/// 8 byte boundary (multiboot 2, as required by the multiboot
+	/// specification) or a 4 byte boundary (multiboot 1).
+	Unaligned,
+
+	/// A tag advertised a `size` smaller than its own 8 byte header, so it
+	/// can't possibly be valid.
+	TagTooSmall,
+
+	/// A read, skip, or tag would have moved the cursor past the end of the
+	/// structure (ie. past `start + total_size`).
+	OutOfBounds,
+}
+
 /// A simple byte reader, which maintains a cursor position within a piece of
 /// memory, with utilities to advance the cursor and read integers of various
 /// sizes.
+///
+/// The reader is bounds checked against an `end` pointer, so a malformed or
+/// truncated multiboot structure returns an error rather than reading (or
+/// faulting on) memory outside the structure.
 struct ByteReader {
 	cursor: *const u8,
+	end: *const u8,
 }
 
 impl ByteReader {
-	/// Returns a new byte reader starting at the given location in memory.
-	fn new(start: *const u8) -> ByteReader {
+	/// Returns a new byte reader starting at `start`, which refuses to read
+	/// or skip past `end`.
+	fn new(start: *const u8, end: *const u8) -> ByteReader {
 		ByteReader {
 			cursor: start,
+			end: end,
 		}
 	}
 
-	/// Moves the cursor forward by a certain number of bytes.
-	unsafe fn skip(&mut self, amount: usize) {
-		self.cursor = self.cursor.offset(amount as isize);
+	/// Moves the cursor forward by a certain number of bytes, failing if this
+	/// would move the cursor past the end of the readable region.
+	unsafe fn skip(&mut self, amount: usize) -> Result<(), MultibootError> {
+		let next = self.cursor.offset(amount as isize);
+		if next > self.end {
+			return Err(MultibootError::OutOfBounds);
+		}
+		self.cursor = next;
+		Ok(())
 	}
 
 	/// Aligns the cursor to the next byte boundary of the given size. `align`
@@ -37,33 +76,36 @@ impl ByteReader {
 	}
 
 	/// Reads a u8 value from memory and advances the cursor by 1 byte.
-	unsafe fn read_u8(&mut self) -> u8 {
+	unsafe fn read_u8(&mut self) -> Result<u8, MultibootError> {
+		if self.cursor.offset(1) > self.end {
+			return Err(MultibootError::OutOfBounds);
+		}
 		let value = *self.cursor;
-		self.skip(1);
-		value
+		self.skip(1)?;
+		Ok(value)
 	}
 
 	/// Reads a u32 value from memory, advancing the cursor by 4 bytes.
 	///
 	/// Assumes we're allowed to read the memory (ie. won't generate a page
 	/// fault), and that the memory contains something valid and useful.
-	unsafe fn read_u32(&mut self) -> u32 {
+	unsafe fn read_u32(&mut self) -> Result<u32, MultibootError> {
 		// Since we're on x86, and all x86 platforms are little-endian, the
 		// u32 value is represented in the multiboot structure as little-endian
 		// (this is also stated in the multiboot specification)
-		self.read_u8() as u32 | (self.read_u8() as u32) << 8 |
-			(self.read_u8() as u32) << 16 | (self.read_u8() as u32) << 24
+		Ok(self.read_u8()? as u32 | (self.read_u8()? as u32) << 8 |
+			(self.read_u8()? as u32) << 16 | (self.read_u8()? as u32) << 24)
 	}
 
 	/// Reads a u64 value from memory, advancing the cursor by 8 bytes.
-	unsafe fn read_u64(&mut self) -> u64 {
+	unsafe fn read_u64(&mut self) -> Result<u64, MultibootError> {
 		// Use a loop and let the compiler unroll it during optimisation
 		// I'm too lazy to write out all 8 or statements explicitly
 		let mut result = 0;
 		for i in 0 .. 8 {
-			result |= (self.read_u8() as u64) << (i << 3);
+			result |= (self.read_u8()? as u64) << (i << 3);
 		}
-		result
+		Ok(result)
 	}
 }
 
@@ -72,44 +114,91 @@ pub struct Multiboot {
 	/// A pointer to the start of the multiboot structure.
 	start: *const u8,
 
+	/// A pointer to the first byte past the end of the multiboot structure,
+	/// ie. `start + total_size`. Every tag lies strictly within
+	/// `[start, end)`.
+	end: *const u8,
+
+	/// A pointer to the first tag in the tag stream, just past the leading
+	/// `total_size`/`reserved` header. Tags that may be absent, or that can
+	/// appear more than once (such as modules), are looked up from here on
+	/// demand rather than cached during `parse`.
+	first_tag: *const u8,
+
 	// Pointers to the start of relevant tags.
 	memory_map: *const u8,
 }
 
 impl Multiboot {
-	/// Create a new multiboot information struct from a pointer to the start
-	/// of one.
-	pub fn new(start: *const u8) -> Multiboot {
+	/// Create a new multiboot information struct from the EAX magic value
+	/// and a pointer to the start of the structure, as left behind by the
+	/// bootloader.
+	///
+	/// Unlike a plain constructor, this validates the magic number, checks
+	/// `start` is properly aligned, and bounds checks every subsequent read
+	/// against the structure's advertised `total_size` before following it.
+	/// A malformed or truncated boot info block returns an error instead of
+	/// faulting the kernel during early boot, when there's no way to recover.
+	pub fn try_new(magic: u32, start: *const u8) -> Result<Multiboot, MultibootError> {
+		if magic != MULTIBOOT2_MAGIC {
+			return Err(MultibootError::InvalidMagic);
+		}
+		if (start as usize) & 7 != 0 {
+			return Err(MultibootError::Unaligned);
+		}
+
 		let mut info = Multiboot {
 			start: start,
+			end: start,
+			first_tag: ptr::null(),
 			memory_map: ptr::null(),
 		};
 
-		// As long as the given pointer is a pointer to a valid multiboot
-		// information struct (an invariant of this function), then this parse
-		// function is safe
-		unsafe { info.parse() };
-		info
+		// As long as `start` is a pointer to a valid multiboot information
+		// struct (an invariant of this function, checked above as best we
+		// can), then this parse function is safe
+		unsafe { info.parse()? };
+		Ok(info)
 	}
 
 	/// Parse the start of relevant tags from a pointer to a multiboot
 	/// information struct.
-	unsafe fn parse(&mut self) {
-		// Read the starting two fields of the struct
-		let mut reader = ByteReader::new(self.start);
-		reader.read_u32(); // total size
-		reader.read_u32(); // reserved
+	unsafe fn parse(&mut self) -> Result<(), MultibootError> {
+		// Read the leading total size field before we know the real bound,
+		// trusting it only as far as its own 8 byte header
+		let mut header = ByteReader::new(self.start, self.start.offset(8));
+		let total_size = header.read_u32()?; // total size
+		header.read_u32()?; // reserved
+
+		if total_size < 8 {
+			return Err(MultibootError::TagTooSmall);
+		}
+
+		// Treat the advertised total size as the hard upper bound for every
+		// subsequent read
+		self.end = self.start.offset(total_size as isize);
+		self.first_tag = header.cursor;
+		let mut reader = ByteReader::new(header.cursor, self.end);
 
 		// Iterate over each tag
 		loop {
-			// Read the tag's type
+			// Read the tag's type and size
 			let cursor = reader.cursor;
-			let kind = reader.read_u32();
-			let size = reader.read_u32();
+			let kind = reader.read_u32()?;
+			let size = reader.read_u32()?;
+
+			// Every tag must be at least big enough to hold its own header,
+			// and must fit entirely within the structure
+			if size < 8 {
+				return Err(MultibootError::TagTooSmall);
+			}
+			if cursor.offset(size as isize) > self.end {
+				return Err(MultibootError::OutOfBounds);
+			}
 
 			// Skip over the tag, subtracting 8 for the 2 u32s we've already
 			// read
-			reader.skip(size as usize - 8);
+			reader.skip(size as usize - 8)?;
 
 			// Each tag is aligned on an 8 byte boundary, so align the cursor
 			// for the next tag to be read
@@ -118,18 +207,479 @@ impl Multiboot {
 			// Depending on the tag's type, set the relevant pointer
 			match kind {
 				6 => self.memory_map = cursor,
-				// 9 => self.elf_symbols = cursor,
 
 				// Stop when we've reached the end of all tags
 				0 => break,
 				_ => {},
 			}
 		}
+
+		Ok(())
 	}
 
 	/// Return an iterator over all valid memory areas.
 	pub fn memory_areas(&self) -> MemoryAreas {
-		MemoryAreas::new(ByteReader::new(self.memory_map))
+		// Without a memory map tag, an empty reader at `end` yields no areas
+		let map = if self.memory_map.is_null() { self.end } else { self.memory_map };
+		MemoryAreas::new(ByteReader::new(map, self.end))
+	}
+
+	/// Return a mutable iterator over all valid memory areas.
+	///
+	/// Early boot code commonly needs to carve the region occupied by the
+	/// kernel image and the boot info structure itself out of the "usable"
+	/// map, or split a usable region around a reserved hole, before handing
+	/// the map off to the frame allocator. The returned iterator mutably
+	/// borrows `self` for `'a`, so `memory_areas()` can't be called while it,
+	/// or any area it yielded, is still alive. An iterator already obtained
+	/// from `memory_areas()`, and the `&'static` areas it yields, hold no
+	/// borrow of `self`, so the borrow checker doesn't stop them being kept
+	/// alive alongside this one.
+	pub fn memory_areas_mut<'a>(&'a mut self) -> MemoryAreasMut<'a> {
+		// Without a memory map tag, an empty reader at `end` yields no areas
+		let map = if self.memory_map.is_null() { self.end } else { self.memory_map };
+		MemoryAreasMut::new(ByteReader::new(map, self.end))
+	}
+
+	/// Returns the boot command line, if the bootloader provided one.
+	pub fn cmdline(&self) -> Option<&str> {
+		self.find_str_tag(1)
+	}
+
+	/// Returns the name of the bootloader that loaded the kernel, if it
+	/// provided one.
+	pub fn bootloader_name(&self) -> Option<&str> {
+		self.find_str_tag(2)
+	}
+
+	/// Returns an iterator over all boot modules loaded alongside the kernel.
+	pub fn modules(&self) -> Modules {
+		Modules::new(ByteReader::new(self.first_tag, self.end))
+	}
+
+	/// Returns the framebuffer info tag, if the bootloader set up a
+	/// framebuffer.
+	pub fn framebuffer(&self) -> Option<Framebuffer> {
+		let (ptr, _) = self.find_tag(8)?;
+		let mut reader = ByteReader::new(ptr, self.end);
+		unsafe {
+			Some(Framebuffer {
+				addr: reader.read_u64().ok()?,
+				pitch: reader.read_u32().ok()?,
+				width: reader.read_u32().ok()?,
+				height: reader.read_u32().ok()?,
+				bpp: reader.read_u8().ok()?,
+				kind: reader.read_u8().ok()?,
+			})
+		}
+	}
+
+	/// Returns an iterator over the kernel's own ELF section headers, as
+	/// recorded by the bootloader in the type 9 tag, or `None` if it didn't
+	/// provide one. This lets the kernel work out which physical ranges hold
+	/// its own `.text`/`.rodata`/`.data`/`.bss`, so those frames can be
+	/// reserved when building the frame allocator from `memory_areas`.
+	pub fn elf_sections(&self) -> Option<ElfSections> {
+		let (ptr, len) = self.find_tag(9)?;
+		let mut reader = ByteReader::new(ptr, self.end);
+		let num = unsafe { reader.read_u32().ok()? } as usize;
+		let entsize = unsafe { reader.read_u32().ok()? };
+		let shndx = unsafe { reader.read_u32().ok()? } as usize;
+		let headers = reader.cursor;
+
+		if shndx >= num {
+			return None;
+		}
+
+		// `len` is the tag's own body length; the only thing trustworthy
+		// here is the tag's advertised `size`, so make sure `num` entries of
+		// `entsize` bytes each actually fit inside it before trusting them.
+		// Without this, a tag could declare a `num` far larger than it has
+		// room for, and indexing into `headers` by `shndx` would walk past
+		// the tag into whatever memory happens to follow
+		let body_len = len.checked_sub(12)?;
+		let headers_len = num.checked_mul(entsize as usize)?;
+		if headers_len > body_len {
+			return None;
+		}
+		let headers_end = unsafe { headers.offset(headers_len as isize) };
+
+		// The ELF-symbols tag is class-agnostic: a 32-bit kernel reports
+		// Elf32_Shdr entries (flags/addr/offset/size are 32-bit), a 64-bit
+		// kernel reports Elf64_Shdr entries (those fields are 64-bit). Use
+		// `entsize` to tell the two apart rather than assuming ELF64, and
+		// bail on anything else rather than silently misreading the fields
+		let is64 = match entsize {
+			64 => true,
+			40 => false,
+			_ => return None,
+		};
+
+		// The string table section's `addr` field is patched by the
+		// bootloader to point at its in-memory data (which it copies into
+		// the tag alongside the section headers themselves), so we can
+		// resolve it up front by indexing straight into the header array
+		let strtab = unsafe {
+			let mut shstrtab = ByteReader::new(
+				headers.offset((shndx * entsize as usize) as isize), headers_end);
+			shstrtab.read_u32().ok()?; // name
+			shstrtab.read_u32().ok()?; // type
+			if is64 {
+				shstrtab.read_u64().ok()?; // flags
+				shstrtab.read_u64().ok()? as *const u8 // addr
+			} else {
+				shstrtab.read_u32().ok()?; // flags
+				shstrtab.read_u32().ok()? as *const u8 // addr
+			}
+		};
+
+		// The resolved pointer is attacker/corruption-controlled data, not
+		// just an offset we've bounds checked; reject it unless it actually
+		// lies within the multiboot structure itself, where a well-formed
+		// bootloader places the strtab's bytes
+		if strtab < self.start || strtab >= self.end {
+			return None;
+		}
+
+		Some(ElfSections {
+			reader: ByteReader::new(headers, headers_end),
+			end: self.end,
+			entsize: entsize,
+			num: num,
+			current: 0,
+			strtab: strtab,
+			is64: is64,
+		})
+	}
+
+	/// Returns the basic memory info tag (the amount of lower and upper
+	/// memory, in kilobytes), if the bootloader provided one.
+	pub fn basic_meminfo(&self) -> Option<BasicMeminfo> {
+		let (ptr, _) = self.find_tag(4)?;
+		let mut reader = ByteReader::new(ptr, self.end);
+		unsafe {
+			Some(BasicMeminfo {
+				mem_lower: reader.read_u32().ok()?,
+				mem_upper: reader.read_u32().ok()?,
+			})
+		}
+	}
+
+	/// Scans the tag stream for the first tag of the given type, returning a
+	/// pointer to its body (just past the 4 byte type and size fields) and
+	/// the body's length in bytes. Returns `None` if no such tag is present.
+	fn find_tag(&self, kind: u32) -> Option<(*const u8, usize)> {
+		let mut reader = ByteReader::new(self.first_tag, self.end);
+		loop {
+			let tag_kind = unsafe { reader.read_u32().ok()? };
+			let size = unsafe { reader.read_u32().ok()? };
+			if tag_kind == 0 || size < 8 {
+				return None;
+			}
+			if tag_kind == kind {
+				return Some((reader.cursor, size as usize - 8));
+			}
+			unsafe {
+				reader.skip(size as usize - 8).ok()?;
+				reader.align(8);
+			}
+		}
+	}
+
+	/// Scans the tag stream for the first tag of the given type, interpreting
+	/// its body as a NUL-terminated string.
+	fn find_str_tag(&self, kind: u32) -> Option<&'static str> {
+		let (ptr, len) = self.find_tag(kind)?;
+		let bytes = unsafe { core::slice::from_raw_parts(ptr, len) };
+		let end = bytes.iter().position(|&b| b == 0).unwrap_or(len);
+		core::str::from_utf8(&bytes[.. end]).ok()
+	}
+}
+
+/// A boot module loaded alongside the kernel by the bootloader, as described
+/// by a type 3 tag.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Module {
+	start: u64,
+	end: u64,
+	cmdline: &'static str,
+}
+
+impl Module {
+	/// Returns the physical address of the start of the module.
+	pub fn start(&self) -> u64 {
+		self.start
+	}
+
+	/// Returns the physical address of the end of the module.
+	pub fn end(&self) -> u64 {
+		self.end
+	}
+
+	/// Returns the module's command line string.
+	pub fn cmdline(&self) -> &'static str {
+		self.cmdline
+	}
+}
+
+/// An iterator over all boot modules in the tag stream.
+pub struct Modules {
+	reader: ByteReader,
+}
+
+impl Modules {
+	/// Create a new module iterator using a byte reader that points to the
+	/// start of the tag stream in the multiboot information struct.
+	fn new(reader: ByteReader) -> Modules {
+		Modules {
+			reader: reader,
+		}
+	}
+}
+
+impl Iterator for Modules {
+	type Item = Module;
+
+	fn next(&mut self) -> Option<Module> {
+		// Walk the tag stream, skipping over anything that isn't a module
+		// tag, until we find one or run out of tags
+		loop {
+			let kind = unsafe { self.reader.read_u32().ok()? };
+			let size = unsafe { self.reader.read_u32().ok()? };
+			if kind == 0 || size < 8 {
+				return None;
+			}
+
+			// A module tag also carries `mod_start`/`mod_end` ahead of its
+			// command line, so it must be at least 16 bytes, not just 8
+			if kind != 3 || size < 16 {
+				unsafe {
+					self.reader.skip(size as usize - 8).ok()?;
+					self.reader.align(8);
+				}
+				continue;
+			}
+
+			let start = unsafe { self.reader.read_u32().ok()? } as u64;
+			let end = unsafe { self.reader.read_u32().ok()? } as u64;
+
+			// The module's command line fills the rest of the tag, as a
+			// NUL-terminated string
+			let len = size as usize - 16;
+			let ptr = self.reader.cursor;
+			let bytes = unsafe { core::slice::from_raw_parts(ptr, len) };
+			let str_end = bytes.iter().position(|&b| b == 0).unwrap_or(len);
+			let cmdline = core::str::from_utf8(&bytes[.. str_end]).unwrap_or("");
+
+			unsafe {
+				self.reader.skip(len).ok()?;
+				self.reader.align(8);
+			}
+
+			return Some(Module {
+				start: start,
+				end: end,
+				cmdline: cmdline,
+			});
+		}
+	}
+}
+
+/// Reads a NUL-terminated string starting at `ptr`, stopping at `end` if no
+/// NUL byte is found first (or if `ptr` is already out of bounds).
+unsafe fn str_from_nul(ptr: *const u8, end: *const u8) -> &'static str {
+	if ptr >= end {
+		return "";
+	}
+	let max_len = end as usize - ptr as usize;
+	let bytes = core::slice::from_raw_parts(ptr, max_len);
+	let len = bytes.iter().position(|&b| b == 0).unwrap_or(max_len);
+	core::str::from_utf8(&bytes[.. len]).unwrap_or("")
+}
+
+/// A single ELF section header from the kernel's own ELF image, as reported
+/// by the type 9 tag.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ElfSection {
+	name: &'static str,
+	kind: u32,
+	flags: u64,
+	addr: u64,
+	size: u64,
+}
+
+impl ElfSection {
+	/// Returns the section's name (eg. `.text`, `.rodata`).
+	pub fn name(&self) -> &'static str {
+		self.name
+	}
+
+	/// Returns the raw ELF section type (`sh_type`).
+	pub fn kind(&self) -> u32 {
+		self.kind
+	}
+
+	/// Returns the raw ELF section flags (`sh_flags`).
+	pub fn flags(&self) -> u64 {
+		self.flags
+	}
+
+	/// Returns the physical address the section was loaded at.
+	pub fn addr(&self) -> u64 {
+		self.addr
+	}
+
+	/// Returns the size of the section, in bytes.
+	pub fn size(&self) -> u64 {
+		self.size
+	}
+}
+
+/// An iterator over the kernel's own ELF section headers.
+pub struct ElfSections {
+	reader: ByteReader,
+
+	/// The end of the multiboot structure, used to bound the string table
+	/// lookup for each section's name.
+	end: *const u8,
+
+	/// The size of each section header entry, given in the tag header, used
+	/// for compatability with other ELF classes.
+	entsize: u32,
+
+	/// The number of section headers.
+	num: usize,
+
+	/// The index of the current section header that we're up to.
+	current: usize,
+
+	/// A pointer to the in-memory data of the section header string table,
+	/// used to resolve each section's name.
+	strtab: *const u8,
+
+	/// Whether each entry is a 64-bit `Elf64_Shdr` (as opposed to a 32-bit
+	/// `Elf32_Shdr`), determined from `entsize` when the iterator was
+	/// created.
+	is64: bool,
+}
+
+impl Iterator for ElfSections {
+	type Item = ElfSection;
+
+	fn next(&mut self) -> Option<ElfSection> {
+		if self.current >= self.num {
+			return None;
+		}
+		self.current += 1;
+
+		// Read the fields we care about from the front of the entry, then
+		// jump back and skip over the whole entry using its own size, since
+		// `entsize` may be larger than the fields we've read
+		let entry = self.reader.cursor;
+		let name_offset = unsafe { self.reader.read_u32().ok()? };
+		let kind = unsafe { self.reader.read_u32().ok()? };
+
+		// Elf64_Shdr's flags/addr/offset/size fields are 64-bit; Elf32_Shdr's
+		// equivalents are 32-bit
+		let (flags, addr, size) = if self.is64 {
+			unsafe {
+				let flags = self.reader.read_u64().ok()?;
+				let addr = self.reader.read_u64().ok()?;
+				self.reader.skip(8).ok()?; // sh_offset
+				let size = self.reader.read_u64().ok()?;
+				(flags, addr, size)
+			}
+		} else {
+			unsafe {
+				let flags = self.reader.read_u32().ok()? as u64;
+				let addr = self.reader.read_u32().ok()? as u64;
+				self.reader.skip(4).ok()?; // sh_offset
+				let size = self.reader.read_u32().ok()? as u64;
+				(flags, addr, size)
+			}
+		};
+
+		unsafe {
+			self.reader.cursor = entry;
+			self.reader.skip(self.entsize as usize).ok()?;
+		}
+
+		let name = unsafe {
+			str_from_nul(self.strtab.offset(name_offset as isize), self.end)
+		};
+
+		Some(ElfSection {
+			name: name,
+			kind: kind,
+			flags: flags,
+			addr: addr,
+			size: size,
+		})
+	}
+}
+
+/// The framebuffer info tag, describing a linear framebuffer set up by the
+/// bootloader.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Framebuffer {
+	addr: u64,
+	pitch: u32,
+	width: u32,
+	height: u32,
+	bpp: u8,
+	kind: u8,
+}
+
+impl Framebuffer {
+	/// Returns the physical address of the start of the framebuffer.
+	pub fn addr(&self) -> u64 {
+		self.addr
+	}
+
+	/// Returns the number of bytes per row of the framebuffer.
+	pub fn pitch(&self) -> u32 {
+		self.pitch
+	}
+
+	/// Returns the width of the framebuffer, in pixels.
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	/// Returns the height of the framebuffer, in pixels.
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	/// Returns the number of bits per pixel.
+	pub fn bpp(&self) -> u8 {
+		self.bpp
+	}
+
+	/// Returns the raw framebuffer type, as defined by the multiboot
+	/// specification (eg. indexed, RGB, or EGA text).
+	pub fn kind(&self) -> u8 {
+		self.kind
+	}
+}
+
+/// The basic memory info tag, giving the amount of lower and upper memory
+/// available, in kilobytes, as reported by the BIOS.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BasicMeminfo {
+	mem_lower: u32,
+	mem_upper: u32,
+}
+
+impl BasicMeminfo {
+	/// Returns the amount of lower memory available, in kilobytes.
+	pub fn mem_lower(&self) -> u32 {
+		self.mem_lower
+	}
+
+	/// Returns the amount of upper memory available, in kilobytes.
+	pub fn mem_upper(&self) -> u32 {
+		self.mem_upper
 	}
 }
 
@@ -144,11 +694,26 @@ pub struct MemoryArea {
 	reserved: u32,
 }
 
-/// The type of a memory area.
+/// The type of a memory area, as reported by the multiboot memory map.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum MemoryAreaType {
+	/// Free for general use.
 	Usable,
-	Unusable,
+
+	/// Holds ACPI tables that can be reclaimed (ie. treated as usable) once
+	/// the kernel has finished parsing ACPI.
+	AcpiReclaimable,
+
+	/// Reserved for use by ACPI firmware across the system's lifetime (ACPI
+	/// NVS); must never be touched.
+	AcpiNvs,
+
+	/// Defective RAM, reported by the BIOS as physically faulty.
+	Defective,
+
+	/// Reserved for some other reason (eg. memory-mapped devices, or an
+	/// unrecognised `kind` value).
+	Reserved,
 }
 
 impl MemoryArea {
@@ -162,13 +727,14 @@ impl MemoryArea {
 		self.length
 	}
 
-	/// Returns the type of the memory area. At this stage, only a distinction
-	/// bewteen usable and unusable memory areas is made.
+	/// Returns the type of the memory area.
 	pub fn kind(&self) -> MemoryAreaType {
-		if self.kind == 1 {
-			MemoryAreaType::Usable
-		} else {
-			MemoryAreaType::Unusable
+		match self.kind {
+			1 => MemoryAreaType::Usable,
+			3 => MemoryAreaType::AcpiReclaimable,
+			4 => MemoryAreaType::AcpiNvs,
+			5 => MemoryAreaType::Defective,
+			_ => MemoryAreaType::Reserved,
 		}
 	}
 }
@@ -192,29 +758,44 @@ pub struct MemoryAreas {
 	current_entry: usize,
 }
 
+/// Reads the header of a memory map tag (pointed to by `reader`, which is
+/// advanced past it), returning the size of each entry and the number of
+/// entries. Returns `(0, 0)` — ie. an empty map — if the tag is truncated,
+/// reports a zero entry size, or is otherwise corrupt, rather than faulting
+/// or panicking on a malformed or adversarial boot info block.
+fn read_memory_map_header(reader: &mut ByteReader) -> (u32, usize) {
+	// Read the four header fields without asserting on them, so a corrupt
+	// structure can fall through to the empty-map case below
+	fn read_header(reader: &mut ByteReader) -> Option<(u32, u32)> {
+		unsafe {
+			reader.read_u32().ok()?; // type
+			let total_size = reader.read_u32().ok()?; // size
+			let entry_size = reader.read_u32().ok()?; // entry size
+			reader.read_u32().ok()?; // entry version, always 0
+			Some((total_size, entry_size))
+		}
+	}
+
+	match read_header(reader) {
+		// Subtract 16 from the total tag size to exclude the header fields
+		// (4 u32s)
+		Some((total_size, entry_size)) if entry_size != 0 && total_size >= 16 => {
+			(entry_size, ((total_size - 16) / entry_size) as usize)
+		}
+		_ => (0, 0),
+	}
+}
+
 impl MemoryAreas {
 	/// Create a new memory area iterator using a byte reader that points to the
 	/// start of the memory map tag in the multiboot information struct.
 	fn new(mut reader: ByteReader) -> MemoryAreas {
-		// Read the tag header
-		let total_size; let entry_size;
-		unsafe {
-			reader.read_u32(); // type
-			total_size = reader.read_u32(); // size
-			entry_size = reader.read_u32(); // entry size
-			reader.read_u32(); // entry version, always 0
-		}
-
-		// Calculate the number of entries in the memory map
-		// Subtract 16 from the total tag size to exclude the header fields (4
-		// u32s)
-		let entries_size = total_size - 16;
-		let entry_count = entries_size / entry_size;
+		let (entry_size, entry_count) = read_memory_map_header(&mut reader);
 
 		MemoryAreas {
 			reader: reader,
 			entry_size: entry_size,
-			entry_count: entry_count as usize,
+			entry_count: entry_count,
 			current_entry: 0,
 		}
 	}
@@ -232,11 +813,452 @@ impl Iterator for MemoryAreas {
 		// Increment the entry counter to move to the next entry
 		self.current_entry += 1;
 
-		// Skip over the entry in the reader
+		// Skip over the entry in the reader, stopping early rather than
+		// panicking if the map is truncated partway through
 		let entry_ptr = self.reader.cursor;
-		unsafe { self.reader.skip(self.entry_size as usize) };
+		if unsafe { self.reader.skip(self.entry_size as usize) }.is_err() {
+			self.entry_count = self.current_entry - 1;
+			return None;
+		}
 
 		// Return a pointer to the entry
 		Some(unsafe { &*(entry_ptr as *const MemoryArea) })
 	}
 }
+
+/// A mutable iterator over all valid memory areas, allowing in-place edits
+/// (eg. carving out reserved ranges) before the map is handed off to the
+/// frame allocator.
+///
+/// Items borrow `self` for `'a`, via `_marker`, so the iterator can't outlive
+/// (or be held alongside) the `&'a mut Multiboot` it was created from.
+pub struct MemoryAreasMut<'a> {
+	reader: ByteReader,
+
+	/// The size of each entry in the memory map, given in the memory map tag
+	/// header, used for compatability with future multiboot versions.
+	entry_size: u32,
+
+	/// The number of entries in the memory map.
+	entry_count: usize,
+
+	/// The index of the current entry that we're up to.
+	current_entry: usize,
+
+	_marker: PhantomData<&'a mut Multiboot>,
+}
+
+impl<'a> MemoryAreasMut<'a> {
+	/// Create a new mutable memory area iterator using a byte reader that
+	/// points to the start of the memory map tag in the multiboot
+	/// information struct.
+	fn new(mut reader: ByteReader) -> MemoryAreasMut<'a> {
+		let (entry_size, entry_count) = read_memory_map_header(&mut reader);
+
+		MemoryAreasMut {
+			reader: reader,
+			entry_size: entry_size,
+			entry_count: entry_count,
+			current_entry: 0,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<'a> Iterator for MemoryAreasMut<'a> {
+	type Item = &'a mut MemoryArea;
+
+	fn next(&mut self) -> Option<&'a mut MemoryArea> {
+		// Check if we've read all entries
+		if self.current_entry >= self.entry_count {
+			return None;
+		}
+
+		// Increment the entry counter to move to the next entry
+		self.current_entry += 1;
+
+		// Skip over the entry in the reader, stopping early rather than
+		// panicking if the map is truncated partway through
+		let entry_ptr = self.reader.cursor;
+		if unsafe { self.reader.skip(self.entry_size as usize) }.is_err() {
+			self.entry_count = self.current_entry - 1;
+			return None;
+		}
+
+		// Return a mutable pointer to the entry
+		Some(unsafe { &mut *(entry_ptr as *mut MemoryArea) })
+	}
+}
+
+/// The EAX magic value the bootloader leaves behind when it hands off to the
+/// kernel using the legacy multiboot 1 protocol.
+const MULTIBOOT1_MAGIC: u32 = 0x2BADB002;
+
+// Bits in a multiboot 1 info struct's `flags` field, indicating which of the
+// other fields the bootloader actually filled in.
+const V1_FLAG_MEMINFO: u32 = 1 << 0;
+const V1_FLAG_BOOT_DEVICE: u32 = 1 << 1;
+const V1_FLAG_CMDLINE: u32 = 1 << 2;
+const V1_FLAG_MODS: u32 = 1 << 3;
+const V1_FLAG_MMAP: u32 = 1 << 6;
+const V1_FLAG_BOOTLOADER_NAME: u32 = 1 << 9;
+
+/// The size, in bytes, of the fixed portion of a multiboot 1 info struct that
+/// we read fields out of directly (everything up to and including
+/// `bootloader_name`).
+const V1_HEADER_SIZE: isize = 68;
+
+/// Reads a NUL-terminated string starting at `ptr`. Unlike the multiboot 2
+/// tag strings, a multiboot 1 info struct carries no length to bound this
+/// read against; the bootloader-supplied pointer has to be trusted.
+unsafe fn read_cstr(ptr: *const u8) -> &'static str {
+	let mut len = 0;
+	while *ptr.offset(len) != 0 {
+		len += 1;
+	}
+	let bytes = core::slice::from_raw_parts(ptr, len as usize);
+	core::str::from_utf8(bytes).unwrap_or("")
+}
+
+/// A multiboot 1 information struct, as handed off by bootloaders that don't
+/// support the multiboot 2 tag-based format. Unlike `Multiboot`, this is a
+/// fixed, flags-gated layout: each field is only meaningful if its
+/// corresponding bit is set in `flags`.
+pub struct MultibootV1 {
+	flags: u32,
+	mem_lower: u32,
+	mem_upper: u32,
+	boot_device: u32,
+	cmdline: *const u8,
+	mods_addr: *const u8,
+	mods_count: u32,
+	mmap_addr: *const u8,
+	mmap_length: u32,
+	bootloader_name: *const u8,
+}
+
+impl MultibootV1 {
+	/// Create a new multiboot 1 information struct from the EAX magic value
+	/// and a pointer to the start of the structure, as left behind by the
+	/// bootloader.
+	pub fn try_new(magic: u32, start: *const u8) -> Result<MultibootV1, MultibootError> {
+		if magic != MULTIBOOT1_MAGIC {
+			return Err(MultibootError::InvalidMagic);
+		}
+		if (start as usize) & 3 != 0 {
+			return Err(MultibootError::Unaligned);
+		}
+
+		// As long as `start` is a pointer to a valid multiboot 1 information
+		// struct (an invariant of this function, checked above as best we
+		// can), then this parse function is safe
+		unsafe { MultibootV1::parse(start) }
+	}
+
+	/// Read the fixed header of a multiboot 1 information struct from a
+	/// pointer to its start.
+	unsafe fn parse(start: *const u8) -> Result<MultibootV1, MultibootError> {
+		let mut reader = ByteReader::new(start, start.offset(V1_HEADER_SIZE));
+
+		let flags = reader.read_u32()?;
+		let mem_lower = reader.read_u32()?;
+		let mem_upper = reader.read_u32()?;
+		let boot_device = reader.read_u32()?;
+		let cmdline = reader.read_u32()? as *const u8;
+		let mods_count = reader.read_u32()?;
+		let mods_addr = reader.read_u32()? as *const u8;
+		reader.skip(16)?; // syms: a.out or ELF symbol table info, unused
+		let mmap_length = reader.read_u32()?;
+		let mmap_addr = reader.read_u32()? as *const u8;
+		reader.skip(8)?; // drives_length, drives_addr
+		reader.skip(4)?; // config_table
+		let bootloader_name = reader.read_u32()? as *const u8;
+
+		Ok(MultibootV1 {
+			flags: flags,
+			mem_lower: mem_lower,
+			mem_upper: mem_upper,
+			boot_device: boot_device,
+			cmdline: cmdline,
+			mods_addr: mods_addr,
+			mods_count: mods_count,
+			mmap_addr: mmap_addr,
+			mmap_length: mmap_length,
+			bootloader_name: bootloader_name,
+		})
+	}
+
+	/// Returns the amount of lower memory available, in kilobytes, if the
+	/// bootloader provided it.
+	pub fn mem_lower(&self) -> Option<u32> {
+		if self.flags & V1_FLAG_MEMINFO == 0 {
+			return None;
+		}
+		Some(self.mem_lower)
+	}
+
+	/// Returns the amount of upper memory available, in kilobytes, if the
+	/// bootloader provided it.
+	pub fn mem_upper(&self) -> Option<u32> {
+		if self.flags & V1_FLAG_MEMINFO == 0 {
+			return None;
+		}
+		Some(self.mem_upper)
+	}
+
+	/// Returns the BIOS boot device code, if the bootloader provided one.
+	pub fn boot_device(&self) -> Option<u32> {
+		if self.flags & V1_FLAG_BOOT_DEVICE == 0 {
+			return None;
+		}
+		Some(self.boot_device)
+	}
+
+	/// Returns the boot command line, if the bootloader provided one.
+	pub fn cmdline(&self) -> Option<&str> {
+		if self.flags & V1_FLAG_CMDLINE == 0 {
+			return None;
+		}
+		Some(unsafe { read_cstr(self.cmdline) })
+	}
+
+	/// Returns the name of the bootloader that loaded the kernel, if it
+	/// provided one.
+	pub fn bootloader_name(&self) -> Option<&str> {
+		if self.flags & V1_FLAG_BOOTLOADER_NAME == 0 {
+			return None;
+		}
+		Some(unsafe { read_cstr(self.bootloader_name) })
+	}
+
+	/// Returns an iterator over all boot modules, or an empty iterator if
+	/// the bootloader didn't provide any.
+	pub fn modules(&self) -> ModulesV1 {
+		if self.flags & V1_FLAG_MODS == 0 {
+			return ModulesV1::empty();
+		}
+		ModulesV1::new(self.mods_addr, self.mods_count as usize)
+	}
+
+	/// Returns an iterator over all valid memory areas, or an empty iterator
+	/// if the bootloader didn't provide a memory map.
+	pub fn memory_areas(&self) -> MemoryAreasV1 {
+		if self.flags & V1_FLAG_MMAP == 0 {
+			return MemoryAreasV1::empty();
+		}
+		MemoryAreasV1::new(self.mmap_addr, self.mmap_length)
+	}
+}
+
+/// An iterator over all boot modules in a multiboot 1 info struct.
+pub struct ModulesV1 {
+	cursor: *const u8,
+	remaining: usize,
+}
+
+impl ModulesV1 {
+	/// Create a new module iterator over `count` entries starting at `start`.
+	fn new(start: *const u8, count: usize) -> ModulesV1 {
+		ModulesV1 {
+			cursor: start,
+			remaining: count,
+		}
+	}
+
+	/// Create an iterator that immediately yields no modules.
+	fn empty() -> ModulesV1 {
+		ModulesV1 {
+			cursor: ptr::null(),
+			remaining: 0,
+		}
+	}
+}
+
+impl Iterator for ModulesV1 {
+	type Item = Module;
+
+	fn next(&mut self) -> Option<Module> {
+		if self.remaining == 0 {
+			return None;
+		}
+		self.remaining -= 1;
+
+		// Each entry is four packed u32 fields: start, end, a pointer to the
+		// module's command line, and a reserved field we ignore
+		unsafe {
+			let mut reader = ByteReader::new(self.cursor, self.cursor.offset(16));
+			let start = reader.read_u32().ok()? as u64;
+			let end = reader.read_u32().ok()? as u64;
+			let cmdline_ptr = reader.read_u32().ok()? as *const u8;
+
+			self.cursor = self.cursor.offset(16);
+
+			Some(Module {
+				start: start,
+				end: end,
+				cmdline: read_cstr(cmdline_ptr),
+			})
+		}
+	}
+}
+
+/// An iterator over all valid memory areas in a multiboot 1 info struct.
+///
+/// Unlike the multiboot 2 memory map, entries aren't a fixed stride apart;
+/// each carries its own leading `size` field and the iterator must advance
+/// by `size + 4` (the 4 bytes being the size field itself) to reach the next
+/// one.
+pub struct MemoryAreasV1 {
+	cursor: *const u8,
+	end: *const u8,
+}
+
+impl MemoryAreasV1 {
+	/// Create a new memory area iterator over the `length` byte region
+	/// starting at `start`.
+	fn new(start: *const u8, length: u32) -> MemoryAreasV1 {
+		MemoryAreasV1 {
+			cursor: start,
+			end: unsafe { start.offset(length as isize) },
+		}
+	}
+
+	/// Create an iterator that immediately yields no memory areas.
+	fn empty() -> MemoryAreasV1 {
+		MemoryAreasV1 {
+			cursor: ptr::null(),
+			end: ptr::null(),
+		}
+	}
+}
+
+impl Iterator for MemoryAreasV1 {
+	type Item = MemoryArea;
+
+	fn next(&mut self) -> Option<MemoryArea> {
+		if self.cursor >= self.end {
+			return None;
+		}
+
+		// Each entry is `size: u32, base: u64, length: u64, type: u32`,
+		// where `size` doesn't include itself
+		unsafe {
+			let mut reader = ByteReader::new(self.cursor, self.end);
+			let size = reader.read_u32().ok()?;
+			let base = reader.read_u64().ok()?;
+			let length = reader.read_u64().ok()?;
+			let kind = reader.read_u32().ok()?;
+
+			// Validate the stride with checked, unsigned arithmetic before
+			// committing to it: `size` is untrusted bootloader data, and
+			// computing `self.cursor.offset(4 + size as isize)` directly
+			// would reinterpret `size`'s sign bit on a 32-bit target, letting
+			// a corrupt `size` near `u32::MAX` leave the cursor unmoved (or
+			// move it backwards) and the iterator loop forever
+			let stride = 4usize.checked_add(size as usize)?;
+			let remaining = self.end as usize - self.cursor as usize;
+			if stride > remaining {
+				self.cursor = self.end;
+				return None;
+			}
+			self.cursor = self.cursor.add(stride);
+
+			Some(MemoryArea {
+				base_addr: base,
+				length: length,
+				kind: kind,
+				reserved: 0,
+			})
+		}
+	}
+}
+
+/// A boot info structure, handed off by the bootloader using either the
+/// multiboot 1 or multiboot 2 protocol. This lets kernel init code stay
+/// agnostic to which protocol the bootloader actually used.
+pub enum BootInfo {
+	V1(MultibootV1),
+	V2(Multiboot),
+}
+
+impl BootInfo {
+	/// Parse a boot info structure from the EAX magic value and pointer left
+	/// behind by the bootloader, picking the multiboot 1 or 2 parser
+	/// depending on which magic number matches.
+	pub fn try_new(magic: u32, start: *const u8) -> Result<BootInfo, MultibootError> {
+		match magic {
+			MULTIBOOT2_MAGIC => Multiboot::try_new(magic, start).map(BootInfo::V2),
+			MULTIBOOT1_MAGIC => MultibootV1::try_new(magic, start).map(BootInfo::V1),
+			_ => Err(MultibootError::InvalidMagic),
+		}
+	}
+
+	/// Returns the boot command line, if the bootloader provided one.
+	pub fn cmdline(&self) -> Option<&str> {
+		match self {
+			BootInfo::V1(info) => info.cmdline(),
+			BootInfo::V2(info) => info.cmdline(),
+		}
+	}
+
+	/// Returns the name of the bootloader that loaded the kernel, if it
+	/// provided one.
+	pub fn bootloader_name(&self) -> Option<&str> {
+		match self {
+			BootInfo::V1(info) => info.bootloader_name(),
+			BootInfo::V2(info) => info.bootloader_name(),
+		}
+	}
+
+	/// Returns an iterator over all valid memory areas.
+	pub fn memory_areas(&self) -> MemoryAreasIter {
+		match self {
+			BootInfo::V1(info) => MemoryAreasIter::V1(info.memory_areas()),
+			BootInfo::V2(info) => MemoryAreasIter::V2(info.memory_areas()),
+		}
+	}
+
+	/// Returns an iterator over all boot modules loaded alongside the kernel.
+	pub fn modules(&self) -> ModulesIter {
+		match self {
+			BootInfo::V1(info) => ModulesIter::V1(info.modules()),
+			BootInfo::V2(info) => ModulesIter::V2(info.modules()),
+		}
+	}
+}
+
+/// An iterator over memory areas that's agnostic to whether the underlying
+/// protocol was multiboot 1 or 2.
+pub enum MemoryAreasIter {
+	V1(MemoryAreasV1),
+	V2(MemoryAreas),
+}
+
+impl Iterator for MemoryAreasIter {
+	type Item = MemoryArea;
+
+	fn next(&mut self) -> Option<MemoryArea> {
+		match self {
+			MemoryAreasIter::V1(iter) => iter.next(),
+			MemoryAreasIter::V2(iter) => iter.next().map(|area| *area),
+		}
+	}
+}
+
+/// An iterator over boot modules that's agnostic to whether the underlying
+/// protocol was multiboot 1 or 2.
+pub enum ModulesIter {
+	V1(ModulesV1),
+	V2(Modules),
+}
+
+impl Iterator for ModulesIter {
+	type Item = Module;
+
+	fn next(&mut self) -> Option<Module> {
+		match self {
+			ModulesIter::V1(iter) => iter.next(),
+			ModulesIter::V2(iter) => iter.next(),
+		}
+	}
+}